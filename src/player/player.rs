@@ -1,10 +1,13 @@
 use futures::channel::mpsc::UnboundedReceiver;
+use futures::future::{abortable, AbortHandle, Aborted, BoxFuture};
 use futures::stream::StreamExt;
+use futures::FutureExt;
 
 use librespot::core::authentication::Credentials;
 use librespot::core::config::SessionConfig;
 use librespot::core::keymaster;
 use librespot::core::session::{Session, SessionError};
+use librespot::core::spotify_id::SpotifyId;
 
 use librespot::playback::mixer::softmixer::SoftMixer;
 use librespot::playback::mixer::{Mixer, MixerConfig};
@@ -18,7 +21,10 @@ use std::cell::RefCell;
 use std::error::Error;
 use std::fmt;
 use std::rc::Rc;
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, SystemTime};
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
 
 use super::Command;
 use crate::app::credentials;
@@ -75,179 +81,501 @@ impl Default for SpotifyPlayerSettings {
     }
 }
 
-pub struct SpotifyPlayer {
+/// Everything `SpotifyPlayer` needs from librespot, pulled behind a trait so
+/// the command-dispatch logic in `handle` can be driven in tests with a fake
+/// implementation instead of a real network connection and audio device.
+pub trait SpotifyBackend: Clone + 'static {
+    type Session: Clone;
+    type Player: PlayerControl;
+
+    fn connect(
+        &self,
+        credentials: Credentials,
+        ap_port: Option<u16>,
+    ) -> BoxFuture<'static, Result<Self::Session, SpotifyError>>;
+
+    fn get_token(
+        &self,
+        session: &Self::Session,
+    ) -> BoxFuture<'static, Result<(String, SystemTime), SpotifyError>>;
+
+    fn create_player(
+        &self,
+        settings: &SpotifyPlayerSettings,
+        mixer: &mut Option<Box<dyn Mixer>>,
+        session: Self::Session,
+    ) -> (Self::Player, PlayerEventChannel);
+
+    fn username(&self, session: &Self::Session) -> String;
+    fn country(&self, session: &Self::Session) -> String;
+    fn shutdown(&self, session: Self::Session);
+}
+
+/// The handful of `librespot::playback::player::Player` operations `handle`
+/// drives. Kept separate from `SpotifyBackend` so a fake player can record
+/// what was asked of it without doing any real decoding.
+pub trait PlayerControl {
+    fn play(&self);
+    fn pause(&self);
+    fn stop(&self);
+    fn seek(&self, position_ms: u32);
+    fn load(&mut self, track_id: SpotifyId, start_playing: bool, position_ms: u32);
+}
+
+impl PlayerControl for Player {
+    fn play(&self) {
+        Player::play(self)
+    }
+
+    fn pause(&self) {
+        Player::pause(self)
+    }
+
+    fn stop(&self) {
+        Player::stop(self)
+    }
+
+    fn seek(&self, position_ms: u32) {
+        Player::seek(self, position_ms)
+    }
+
+    fn load(&mut self, track_id: SpotifyId, start_playing: bool, position_ms: u32) {
+        Player::load(self, track_id, start_playing, position_ms)
+    }
+}
+
+/// The real backend, talking to Spotify over the network through librespot.
+#[derive(Debug, Clone, Default)]
+pub struct LibrespotBackend;
+
+impl SpotifyBackend for LibrespotBackend {
+    type Session = Session;
+    type Player = Player;
+
+    fn connect(
+        &self,
+        credentials: Credentials,
+        ap_port: Option<u16>,
+    ) -> BoxFuture<'static, Result<Session, SpotifyError>> {
+        create_session(credentials, ap_port).boxed()
+    }
+
+    fn get_token(
+        &self,
+        session: &Session,
+    ) -> BoxFuture<'static, Result<(String, SystemTime), SpotifyError>> {
+        let session = session.clone();
+        async move { get_access_token_and_expiry_time(&session).await }.boxed()
+    }
+
+    fn create_player(
+        &self,
+        settings: &SpotifyPlayerSettings,
+        mixer: &mut Option<Box<dyn Mixer>>,
+        session: Session,
+    ) -> (Player, PlayerEventChannel) {
+        let backend = settings.backend.clone();
+
+        let player_config = PlayerConfig {
+            bitrate: settings.bitrate,
+            ..Default::default()
+        };
+        info!("bitrate: {:?}", &player_config.bitrate);
+
+        let soft_volume = mixer
+            .get_or_insert_with(|| {
+                let mix = Box::new(SoftMixer::open(MixerConfig {
+                    // This value feels reasonable to me. Feel free to change it
+                    volume_ctrl: VolumeCtrl::Log(VolumeCtrl::DEFAULT_DB_RANGE / 2.0),
+                    ..Default::default()
+                }));
+                // TODO: Should read volume from somewhere instead of hard coding.
+                // Sets volume to 100%
+                mix.set_volume(VolumeCtrl::MAX_VOLUME);
+                mix
+            })
+            .get_soft_volume();
+
+        Player::new(player_config, session, soft_volume, move || match backend {
+            AudioBackend::PulseAudio => {
+                info!("using pulseaudio");
+                let backend = audio_backend::find(Some("pulseaudio".to_string())).unwrap();
+                backend(None, AudioFormat::default())
+            }
+            AudioBackend::Alsa(device) => {
+                info!("using alsa ({})", &device);
+                let backend = audio_backend::find(Some("alsa".to_string())).unwrap();
+                backend(Some(device), AudioFormat::default())
+            }
+        })
+    }
+
+    fn username(&self, session: &Session) -> String {
+        session.username()
+    }
+
+    fn country(&self, session: &Session) -> String {
+        session.country()
+    }
+
+    fn shutdown(&self, session: Session) {
+        session.shutdown();
+    }
+}
+
+/// A cancellable background task, paired with its abort handle so a newer
+/// task can cancel and wait out an older one before taking over.
+struct AbortableTask {
+    abort_handle: AbortHandle,
+    join_handle: JoinHandle<()>,
+}
+
+impl AbortableTask {
+    async fn cancel(self) {
+        self.abort_handle.abort();
+        let _ = self.join_handle.await;
+    }
+}
+
+// The safety margin subtracted from `token_expiry_time` before scheduling a
+// background refresh, so the token is renewed a little before it actually
+// lapses instead of racing the server's clock.
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+pub struct SpotifyPlayerBuilder<B: SpotifyBackend> {
+    backend: B,
     settings: SpotifyPlayerSettings,
-    player: Option<Player>,
-    mixer: Option<Box<dyn Mixer>>,
-    session: Option<Session>,
-    delegate: Rc<dyn SpotifyPlayerDelegate>,
+    delegate: Option<Arc<dyn SpotifyPlayerDelegate + Send + Sync>>,
+    cached_token: Option<(String, SystemTime)>,
 }
 
-impl SpotifyPlayer {
-    pub fn new(settings: SpotifyPlayerSettings, delegate: Rc<dyn SpotifyPlayerDelegate>) -> Self {
+impl<B: SpotifyBackend> SpotifyPlayerBuilder<B> {
+    pub fn new(backend: B, settings: SpotifyPlayerSettings) -> Self {
         Self {
+            backend,
             settings,
+            delegate: None,
+            cached_token: None,
+        }
+    }
+
+    pub fn delegate(mut self, delegate: Arc<dyn SpotifyPlayerDelegate + Send + Sync>) -> Self {
+        self.delegate = Some(delegate);
+        self
+    }
+
+    pub fn cached_token(mut self, cached_token: Option<(String, SystemTime)>) -> Self {
+        self.cached_token = cached_token;
+        self
+    }
+
+    pub fn build(self) -> SpotifyPlayer<B> {
+        SpotifyPlayer {
+            backend: self.backend,
+            settings: self.settings,
             mixer: None,
             player: None,
             session: None,
-            delegate,
+            pending_setup: None,
+            token_refresh_task: None,
+            token_cache: self.cached_token,
+            delegate: self
+                .delegate
+                .expect("SpotifyPlayerBuilder::build called without a delegate"),
+        }
+    }
+}
+
+pub struct SpotifyPlayer<B: SpotifyBackend> {
+    backend: B,
+    settings: SpotifyPlayerSettings,
+    player: Option<B::Player>,
+    mixer: Option<Box<dyn Mixer>>,
+    session: Option<B::Session>,
+    pending_setup: Option<AbortableTask>,
+    token_refresh_task: Option<AbortableTask>,
+    token_cache: Option<(String, SystemTime)>,
+    delegate: Arc<dyn SpotifyPlayerDelegate + Send + Sync>,
+}
+
+impl<B: SpotifyBackend> SpotifyPlayer<B> {
+    // Aborts whatever session-establishment task is currently running, if
+    // any, and waits for it to actually wind down before returning. This
+    // guarantees only one setup future is ever live at a time.
+    // Takes the pending setup out under a short borrow and awaits its
+    // cancellation afterwards, so the `RefCell` isn't held borrowed across
+    // the await (a second `spawn_local` task touching `self_rc` mid-await
+    // would otherwise panic with "already borrowed").
+    async fn cancel_pending_setup(self_rc: &Rc<RefCell<Self>>) {
+        let pending = self_rc.borrow_mut().pending_setup.take();
+        if let Some(pending) = pending {
+            pending.cancel().await;
         }
     }
 
-    async fn handle(&mut self, action: Command) -> Result<(), SpotifyError> {
+    async fn cancel_token_refresh(self_rc: &Rc<RefCell<Self>>) {
+        let refresh = self_rc.borrow_mut().token_refresh_task.take();
+        if let Some(refresh) = refresh {
+            refresh.cancel().await;
+        }
+    }
+
+    async fn handle(self_rc: &Rc<RefCell<Self>>, action: Command) -> Result<(), SpotifyError> {
         match action {
             Command::PlayerSetVolume(volume) => {
-                if let Some(mixer) = self.mixer.as_mut() {
+                let mut _self = self_rc.borrow_mut();
+                if let Some(mixer) = _self.mixer.as_mut() {
                     mixer.set_volume((VolumeCtrl::MAX_VOLUME as f64 * volume) as u16);
                 }
                 Ok(())
             }
             Command::PlayerResume => {
-                self.player
+                self_rc
+                    .borrow()
+                    .player
                     .as_ref()
                     .ok_or(SpotifyError::PlayerNotReady)?
                     .play();
                 Ok(())
             }
             Command::PlayerPause => {
-                self.player
+                self_rc
+                    .borrow()
+                    .player
                     .as_ref()
                     .ok_or(SpotifyError::PlayerNotReady)?
                     .pause();
                 Ok(())
             }
             Command::PlayerStop => {
-                self.player
+                self_rc
+                    .borrow()
+                    .player
                     .as_ref()
                     .ok_or(SpotifyError::PlayerNotReady)?
                     .stop();
                 Ok(())
             }
             Command::PlayerSeek(position) => {
-                self.player
+                self_rc
+                    .borrow()
+                    .player
                     .as_ref()
                     .ok_or(SpotifyError::PlayerNotReady)?
                     .seek(position);
                 Ok(())
             }
             Command::PlayerLoad(track) => {
-                self.player
+                self_rc
+                    .borrow_mut()
+                    .player
                     .as_mut()
                     .ok_or(SpotifyError::PlayerNotReady)?
                     .load(track, true, 0);
                 Ok(())
             }
             Command::RefreshToken => {
-                let session = self.session.as_ref().ok_or(SpotifyError::PlayerNotReady)?;
-                let (token, token_expiry_time) = get_access_token_and_expiry_time(session).await?;
-                self.delegate.refresh_successful(token, token_expiry_time);
+                let backend = self_rc.borrow().backend.clone();
+                let session = self_rc
+                    .borrow()
+                    .session
+                    .clone()
+                    .ok_or(SpotifyError::PlayerNotReady)?;
+                let (token, token_expiry_time) = backend.get_token(&session).await?;
+
+                let mut _self = self_rc.borrow_mut();
+                _self.token_cache = Some((token.clone(), token_expiry_time));
+                let refresh_task = schedule_token_refresh(self_rc, token_expiry_time);
+                let previous = _self.token_refresh_task.replace(refresh_task);
+                _self.delegate.refresh_successful(token, token_expiry_time);
+                drop(_self);
+                if let Some(previous) = previous {
+                    previous.cancel().await;
+                }
                 Ok(())
             }
             Command::Logout => {
-                self.session
-                    .take()
-                    .ok_or(SpotifyError::PlayerNotReady)?
-                    .shutdown();
-                let _ = self.player.take();
+                SpotifyPlayer::cancel_pending_setup(self_rc).await;
+                SpotifyPlayer::cancel_token_refresh(self_rc).await;
+
+                let mut _self = self_rc.borrow_mut();
+                _self.token_cache = None;
+                let session = _self.session.take().ok_or(SpotifyError::PlayerNotReady)?;
+                _self.backend.shutdown(session);
+                let _ = _self.player.take();
                 Ok(())
             }
             Command::PasswordLogin { username, password } => {
+                SpotifyPlayer::cancel_pending_setup(self_rc).await;
+                SpotifyPlayer::cancel_token_refresh(self_rc).await;
+
                 let credentials = Credentials::with_password(username, password.clone());
-                let new_session = create_session(credentials, self.settings.ap_port).await?;
-                let (token, token_expiry_time) =
-                    get_access_token_and_expiry_time(&new_session).await?;
-                let credentials = credentials::Credentials {
-                    username: new_session.username(),
-                    password,
-                    token,
-                    token_expiry_time: Some(token_expiry_time),
-                    country: new_session.country(),
-                };
-                self.delegate.password_login_successful(credentials);
+                let backend = self_rc.borrow().backend.clone();
+                let ap_port = self_rc.borrow().settings.ap_port;
+                let cached_token = self_rc.borrow().token_cache.clone();
+
+                let setup_backend = backend.clone();
+                let (setup, abort_handle) = abortable(async move {
+                    let session = setup_backend.connect(credentials, ap_port).await?;
+                    let (token, token_expiry_time) = match cached_token {
+                        Some((token, token_expiry_time))
+                            if token_is_still_valid(token_expiry_time) =>
+                        {
+                            (token, token_expiry_time)
+                        }
+                        _ => setup_backend.get_token(&session).await?,
+                    };
+                    Ok::<_, SpotifyError>((session, token, token_expiry_time))
+                });
+
+                let post_setup = Rc::clone(self_rc);
+                let self_rc = Rc::clone(self_rc);
+                let join_handle = tokio::task::spawn_local(async move {
+                    let outcome = match setup.await {
+                        Ok(result) => result,
+                        Err(Aborted) => return,
+                    };
+
+                    match outcome {
+                        Ok((new_session, token, token_expiry_time)) => {
+                            let credentials = credentials::Credentials {
+                                username: backend.username(&new_session),
+                                password,
+                                token: token.clone(),
+                                token_expiry_time: Some(token_expiry_time),
+                                country: backend.country(&new_session),
+                            };
+                            let delegate = Arc::clone(&self_rc.borrow().delegate);
+                            delegate.password_login_successful(credentials);
+
+                            let refresh_task = schedule_token_refresh(&self_rc, token_expiry_time);
+
+                            let mut _self = self_rc.borrow_mut();
+                            let (new_player, channel) = _self.create_player(new_session.clone());
+                            tokio::task::spawn_local(player_setup_delegate(
+                                channel,
+                                Arc::clone(&_self.delegate),
+                            ));
+                            _self.player.replace(new_player);
+                            _self.session.replace(new_session);
+                            _self.token_cache = Some((token, token_expiry_time));
+                            _self.token_refresh_task = Some(refresh_task);
+                            _self.pending_setup = None;
+                        }
+                        Err(err) => {
+                            let mut _self = self_rc.borrow_mut();
+                            _self.pending_setup = None;
+                            _self.delegate.report_error(err);
+                        }
+                    }
+                });
 
-                let (new_player, channel) = self.create_player(new_session.clone());
-                tokio::task::spawn_local(player_setup_delegate(channel, Rc::clone(&self.delegate)));
-                self.player.replace(new_player);
-                self.session.replace(new_session);
+                post_setup.borrow_mut().pending_setup = Some(AbortableTask {
+                    abort_handle,
+                    join_handle,
+                });
 
                 Ok(())
             }
             Command::TokenLogin { username, token } => {
+                SpotifyPlayer::cancel_pending_setup(self_rc).await;
+                SpotifyPlayer::cancel_token_refresh(self_rc).await;
+
                 let credentials = Credentials {
                     username,
                     auth_type: AuthenticationType::AUTHENTICATION_SPOTIFY_TOKEN,
                     auth_data: token.clone().into_bytes(),
                 };
-                let new_session = create_session(credentials, self.settings.ap_port).await?;
-                self.delegate
-                    .token_login_successful(new_session.username(), token);
+                let backend = self_rc.borrow().backend.clone();
+                let ap_port = self_rc.borrow().settings.ap_port;
+
+                let setup_backend = backend.clone();
+                let (setup, abort_handle) =
+                    abortable(async move { setup_backend.connect(credentials, ap_port).await });
+
+                let post_setup = Rc::clone(self_rc);
+                let self_rc = Rc::clone(self_rc);
+                let join_handle = tokio::task::spawn_local(async move {
+                    let outcome = match setup.await {
+                        Ok(result) => result,
+                        Err(Aborted) => return,
+                    };
+
+                    match outcome {
+                        Ok(new_session) => {
+                            let delegate = Arc::clone(&self_rc.borrow().delegate);
+                            delegate.token_login_successful(backend.username(&new_session), token);
 
-                let (new_player, channel) = self.create_player(new_session.clone());
-                tokio::task::spawn_local(player_setup_delegate(channel, Rc::clone(&self.delegate)));
-                self.player.replace(new_player);
-                self.session.replace(new_session);
+                            let mut _self = self_rc.borrow_mut();
+                            let (new_player, channel) = _self.create_player(new_session.clone());
+                            tokio::task::spawn_local(player_setup_delegate(
+                                channel,
+                                Arc::clone(&_self.delegate),
+                            ));
+                            _self.player.replace(new_player);
+                            _self.session.replace(new_session);
+                            // A token handed in directly carries no known
+                            // expiry, so there's nothing to schedule a
+                            // proactive refresh against; drop any cache left
+                            // over from a previous login instead of serving
+                            // it against the new session.
+                            _self.token_cache = None;
+                            _self.pending_setup = None;
+                        }
+                        Err(err) => {
+                            let mut _self = self_rc.borrow_mut();
+                            _self.pending_setup = None;
+                            _self.delegate.report_error(err);
+                        }
+                    }
+                });
+
+                post_setup.borrow_mut().pending_setup = Some(AbortableTask {
+                    abort_handle,
+                    join_handle,
+                });
 
                 Ok(())
             }
             Command::ReloadSettings => {
+                SpotifyPlayer::cancel_pending_setup(self_rc).await;
+
                 let settings = SpotSettings::new_from_gsettings().unwrap_or_default();
-                self.settings = settings.player_settings;
+                let mut _self = self_rc.borrow_mut();
+                _self.settings = settings.player_settings;
 
-                let session = self.session.take().ok_or(SpotifyError::PlayerNotReady)?;
-                let (new_player, channel) = self.create_player(session);
-                tokio::task::spawn_local(player_setup_delegate(channel, Rc::clone(&self.delegate)));
-                self.player.replace(new_player);
+                let session = _self.session.take().ok_or(SpotifyError::PlayerNotReady)?;
+                let (new_player, channel) = _self.create_player(session.clone());
+                tokio::task::spawn_local(player_setup_delegate(channel, Arc::clone(&_self.delegate)));
+                _self.player.replace(new_player);
+                _self.session.replace(session);
 
                 Ok(())
             }
         }
     }
 
-    fn create_player(&mut self, session: Session) -> (Player, PlayerEventChannel) {
-        let backend = self.settings.backend.clone();
-
-        let player_config = PlayerConfig {
-            bitrate: self.settings.bitrate,
-            ..Default::default()
-        };
-        info!("bitrate: {:?}", &player_config.bitrate);
-
-        let soft_volume = self
-            .mixer
-            .get_or_insert_with(|| {
-                let mix = Box::new(SoftMixer::open(MixerConfig {
-                    // This value feels reasonable to me. Feel free to change it
-                    volume_ctrl: VolumeCtrl::Log(VolumeCtrl::DEFAULT_DB_RANGE / 2.0),
-                    ..Default::default()
-                }));
-                // TODO: Should read volume from somewhere instead of hard coding.
-                // Sets volume to 100%
-                mix.set_volume(VolumeCtrl::MAX_VOLUME);
-                mix
-            })
-            .get_soft_volume();
-        Player::new(player_config, session, soft_volume, move || match backend {
-            AudioBackend::PulseAudio => {
-                info!("using pulseaudio");
-                let backend = audio_backend::find(Some("pulseaudio".to_string())).unwrap();
-                backend(None, AudioFormat::default())
-            }
-            AudioBackend::Alsa(device) => {
-                info!("using alsa ({})", &device);
-                let backend = audio_backend::find(Some("alsa".to_string())).unwrap();
-                backend(Some(device), AudioFormat::default())
-            }
-        })
+    fn create_player(&mut self, session: B::Session) -> (B::Player, PlayerEventChannel) {
+        self.backend
+            .create_player(&self.settings, &mut self.mixer, session)
     }
 
+    // Drives the command loop. Spawns all of librespot's own connect/decode
+    // futures onto whatever runtime is polling this future, via a `LocalSet`
+    // since the player state is kept in an `Rc<RefCell<_>>`. Production code
+    // should go through `SpotifyPlayerBuilder::spawn` instead of calling this
+    // directly, so that runtime lives on its own dedicated thread rather than
+    // whichever one happens to be driving the caller.
     pub async fn start(self, receiver: UnboundedReceiver<Command>) -> Result<(), ()> {
-        let _self = RefCell::new(self);
+        let _self = Rc::new(RefCell::new(self));
         receiver
-            .for_each(|action| async {
-                let mut _self = _self.borrow_mut();
-                match _self.handle(action).await {
-                    Ok(_) => {}
-                    Err(err) => _self.delegate.report_error(err),
+            .for_each(|action| {
+                let _self = Rc::clone(&_self);
+                async move {
+                    match SpotifyPlayer::handle(&_self, action).await {
+                        Ok(_) => {}
+                        Err(err) => _self.borrow().delegate.report_error(err),
+                    }
                 }
             })
             .await;
@@ -255,6 +583,47 @@ impl SpotifyPlayer {
     }
 }
 
+// The runtime that hosts every `SpotifyPlayer`. Kept as a single lazily
+// initialized, multi-threaded runtime rather than one per player so that
+// librespot's own internal connect/decode work always has a runtime to land
+// on, instead of trying (and failing) to start one of its own from inside
+// whatever executor is driving the GTK side.
+fn player_runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("spotify-player")
+            .build()
+            .expect("failed to create the Spotify player runtime")
+    })
+}
+
+impl<B: SpotifyBackend + Send> SpotifyPlayerBuilder<B> {
+    /// Builds the player and runs it to completion on a dedicated OS thread
+    /// backed by [`player_runtime`], isolated from the thread that calls this
+    /// (typically the GTK main thread). All communication happens through
+    /// `receiver` and the delegate passed to [`SpotifyPlayerBuilder::delegate`]
+    /// — the delegate implementation is responsible for marshalling its
+    /// callbacks back onto the main context, since they will now be invoked
+    /// from the player thread.
+    pub fn spawn(self, receiver: UnboundedReceiver<Command>) -> std::thread::JoinHandle<()> {
+        std::thread::Builder::new()
+            .name("spotify-player".to_string())
+            .spawn(move || {
+                let player = self.build();
+                let local = tokio::task::LocalSet::new();
+                if player_runtime()
+                    .block_on(local.run_until(player.start(receiver)))
+                    .is_err()
+                {
+                    error!("Spotify player command loop exited with an error");
+                }
+            })
+            .expect("failed to spawn the Spotify player thread")
+    }
+}
+
 const CLIENT_ID: &str = "782ae96ea60f4cdf986a766049607005";
 
 const SCOPES: &str = "user-read-private,\
@@ -325,9 +694,64 @@ async fn create_session(
     }
 }
 
+fn token_is_still_valid(token_expiry_time: SystemTime) -> bool {
+    token_expiry_time
+        .checked_sub(TOKEN_REFRESH_MARGIN)
+        .map(|deadline| deadline > SystemTime::now())
+        .unwrap_or(false)
+}
+
+// Spawns a timer that fires shortly before `token_expiry_time` and issues a
+// refresh against the current session, pushing the new token through
+// `delegate.refresh_successful` and rescheduling itself for the next expiry.
+fn schedule_token_refresh<B: SpotifyBackend>(
+    self_rc: &Rc<RefCell<SpotifyPlayer<B>>>,
+    token_expiry_time: SystemTime,
+) -> AbortableTask {
+    let delay = token_expiry_time
+        .checked_sub(TOKEN_REFRESH_MARGIN)
+        .unwrap_or_else(SystemTime::now)
+        .duration_since(SystemTime::now())
+        .unwrap_or(Duration::ZERO);
+
+    let self_rc = Rc::clone(self_rc);
+    let (refresh, abort_handle) = abortable(async move {
+        tokio::time::sleep(delay).await;
+
+        let (backend, session) = {
+            let _self = self_rc.borrow();
+            match _self.session.clone() {
+                Some(session) => (_self.backend.clone(), session),
+                None => return,
+            }
+        };
+
+        match backend.get_token(&session).await {
+            Ok((token, token_expiry_time)) => {
+                let next_refresh = schedule_token_refresh(&self_rc, token_expiry_time);
+
+                let mut _self = self_rc.borrow_mut();
+                _self.token_cache = Some((token.clone(), token_expiry_time));
+                _self.token_refresh_task = Some(next_refresh);
+                _self.delegate.refresh_successful(token, token_expiry_time);
+            }
+            Err(err) => self_rc.borrow().delegate.report_error(err),
+        }
+    });
+
+    let join_handle = tokio::task::spawn_local(async move {
+        let _ = refresh.await;
+    });
+
+    AbortableTask {
+        abort_handle,
+        join_handle,
+    }
+}
+
 async fn player_setup_delegate(
     mut channel: PlayerEventChannel,
-    delegate: Rc<dyn SpotifyPlayerDelegate>,
+    delegate: Arc<dyn SpotifyPlayerDelegate + Send + Sync>,
 ) {
     while let Some(event) = channel.recv().await {
         match event {
@@ -341,3 +765,327 @@ async fn player_setup_delegate(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell as StdRefCell;
+
+    #[derive(Debug, Clone, Default)]
+    struct FakeSession {
+        username: String,
+    }
+
+    #[derive(Default)]
+    struct FakePlayer {
+        loaded_tracks: Vec<SpotifyId>,
+    }
+
+    impl PlayerControl for FakePlayer {
+        fn play(&self) {}
+        fn pause(&self) {}
+        fn stop(&self) {}
+        fn seek(&self, _position_ms: u32) {}
+        fn load(&mut self, track_id: SpotifyId, _start_playing: bool, _position_ms: u32) {
+            self.loaded_tracks.push(track_id);
+        }
+    }
+
+    #[derive(Clone)]
+    struct FakeBackend {
+        should_fail: bool,
+        player_rebuilds: Rc<StdRefCell<u32>>,
+        connect_gate: Rc<StdRefCell<Option<tokio::sync::oneshot::Receiver<()>>>>,
+    }
+
+    impl FakeBackend {
+        fn new() -> Self {
+            Self {
+                should_fail: false,
+                player_rebuilds: Rc::new(StdRefCell::new(0)),
+                connect_gate: Rc::new(StdRefCell::new(None)),
+            }
+        }
+
+        // Makes the next `connect()` stall until the returned sender fires,
+        // so a test can keep a setup task pending while issuing another command.
+        fn gate_next_connect(&self) -> tokio::sync::oneshot::Sender<()> {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            *self.connect_gate.borrow_mut() = Some(rx);
+            tx
+        }
+    }
+
+    impl SpotifyBackend for FakeBackend {
+        type Session = FakeSession;
+        type Player = FakePlayer;
+
+        fn connect(
+            &self,
+            credentials: Credentials,
+            _ap_port: Option<u16>,
+        ) -> BoxFuture<'static, Result<Self::Session, SpotifyError>> {
+            let should_fail = self.should_fail;
+            let gate = self.connect_gate.borrow_mut().take();
+            async move {
+                if let Some(gate) = gate {
+                    let _ = gate.await;
+                }
+                if should_fail {
+                    return Err(SpotifyError::LoginFailed);
+                }
+                Ok(FakeSession {
+                    username: credentials.username,
+                })
+            }
+            .boxed()
+        }
+
+        fn get_token(
+            &self,
+            _session: &Self::Session,
+        ) -> BoxFuture<'static, Result<(String, SystemTime), SpotifyError>> {
+            async move { Ok(("fake-token".to_string(), SystemTime::now())) }.boxed()
+        }
+
+        fn create_player(
+            &self,
+            _settings: &SpotifyPlayerSettings,
+            _mixer: &mut Option<Box<dyn Mixer>>,
+            _session: Self::Session,
+        ) -> (Self::Player, PlayerEventChannel) {
+            *self.player_rebuilds.borrow_mut() += 1;
+            let (_sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+            (FakePlayer::default(), receiver)
+        }
+
+        fn username(&self, session: &Self::Session) -> String {
+            session.username.clone()
+        }
+
+        fn country(&self, _session: &Self::Session) -> String {
+            "US".to_string()
+        }
+
+        fn shutdown(&self, _session: Self::Session) {}
+    }
+
+    struct FakeDelegate {
+        errors: std::sync::Mutex<Vec<String>>,
+        logins: std::sync::atomic::AtomicU32,
+        refreshes: std::sync::atomic::AtomicU32,
+    }
+
+    impl FakeDelegate {
+        fn new() -> Self {
+            Self {
+                errors: std::sync::Mutex::new(Vec::new()),
+                logins: std::sync::atomic::AtomicU32::new(0),
+                refreshes: std::sync::atomic::AtomicU32::new(0),
+            }
+        }
+    }
+
+    impl SpotifyPlayerDelegate for FakeDelegate {
+        fn end_of_track_reached(&self) {}
+        fn password_login_successful(&self, _credentials: credentials::Credentials) {
+            self.logins.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+        fn token_login_successful(&self, _username: String, _token: String) {
+            self.logins.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+        fn refresh_successful(&self, _token: String, _token_expiry_time: SystemTime) {
+            self.refreshes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+        fn report_error(&self, error: SpotifyError) {
+            self.errors.lock().unwrap().push(error.to_string());
+        }
+        fn notify_playback_state(&self, _position: u32) {}
+    }
+
+    async fn drain_once(self_rc: &Rc<RefCell<SpotifyPlayer<FakeBackend>>>, action: Command) {
+        let _ = SpotifyPlayer::handle(self_rc, action).await;
+        // Give any spawned setup task a chance to run to completion.
+        tokio::task::yield_now().await;
+        tokio::task::yield_now().await;
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn player_load_before_login_is_not_ready() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let delegate = Arc::new(FakeDelegate::new());
+                let player = SpotifyPlayerBuilder::new(FakeBackend::new(), SpotifyPlayerSettings::default())
+                    .delegate(Arc::clone(&delegate) as Arc<dyn SpotifyPlayerDelegate + Send + Sync>)
+                    .build();
+                let self_rc = Rc::new(RefCell::new(player));
+
+                let track = SpotifyId::from_base62("4iV5W9uYEdYUVa79Axb7Rh").unwrap();
+                let result = SpotifyPlayer::handle(&self_rc, Command::PlayerLoad(track)).await;
+                assert!(matches!(result, Err(SpotifyError::PlayerNotReady)));
+            })
+            .await;
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn password_login_success_notifies_delegate() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let delegate = Arc::new(FakeDelegate::new());
+                let player = SpotifyPlayerBuilder::new(FakeBackend::new(), SpotifyPlayerSettings::default())
+                    .delegate(Arc::clone(&delegate) as Arc<dyn SpotifyPlayerDelegate + Send + Sync>)
+                    .build();
+                let self_rc = Rc::new(RefCell::new(player));
+
+                drain_once(
+                    &self_rc,
+                    Command::PasswordLogin {
+                        username: "alice".to_string(),
+                        password: "secret".to_string(),
+                    },
+                )
+                .await;
+
+                assert_eq!(delegate.logins.load(std::sync::atomic::Ordering::SeqCst), 1);
+                assert!(delegate.errors.lock().unwrap().is_empty());
+                assert!(self_rc.borrow().session.is_some());
+                assert!(self_rc.borrow().player.is_some());
+            })
+            .await;
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn reload_settings_rebuilds_player_but_keeps_session() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let delegate = Arc::new(FakeDelegate::new());
+                let backend = FakeBackend::new();
+                let player = SpotifyPlayerBuilder::new(backend.clone(), SpotifyPlayerSettings::default())
+                    .delegate(Arc::clone(&delegate) as Arc<dyn SpotifyPlayerDelegate + Send + Sync>)
+                    .build();
+                let self_rc = Rc::new(RefCell::new(player));
+
+                drain_once(
+                    &self_rc,
+                    Command::PasswordLogin {
+                        username: "alice".to_string(),
+                        password: "secret".to_string(),
+                    },
+                )
+                .await;
+
+                let username_before = {
+                    let _self = self_rc.borrow();
+                    backend.username(_self.session.as_ref().unwrap())
+                };
+
+                let _ = SpotifyPlayer::handle(&self_rc, Command::ReloadSettings).await;
+
+                let username_after = {
+                    let _self = self_rc.borrow();
+                    backend.username(_self.session.as_ref().unwrap())
+                };
+
+                assert_eq!(username_before, username_after);
+                assert_eq!(*backend.player_rebuilds.borrow(), 2);
+            })
+            .await;
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn second_password_login_aborts_the_first_pending_setup() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let delegate = Arc::new(FakeDelegate::new());
+                let backend = FakeBackend::new();
+                let player = SpotifyPlayerBuilder::new(backend.clone(), SpotifyPlayerSettings::default())
+                    .delegate(Arc::clone(&delegate) as Arc<dyn SpotifyPlayerDelegate + Send + Sync>)
+                    .build();
+                let self_rc = Rc::new(RefCell::new(player));
+
+                let release_first_connect = backend.gate_next_connect();
+
+                let _ = SpotifyPlayer::handle(
+                    &self_rc,
+                    Command::PasswordLogin {
+                        username: "alice".to_string(),
+                        password: "secret".to_string(),
+                    },
+                )
+                .await;
+                tokio::task::yield_now().await;
+                tokio::task::yield_now().await;
+
+                assert!(self_rc.borrow().pending_setup.is_some());
+                assert_eq!(delegate.logins.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+                drain_once(
+                    &self_rc,
+                    Command::PasswordLogin {
+                        username: "bob".to_string(),
+                        password: "hunter2".to_string(),
+                    },
+                )
+                .await;
+
+                assert_eq!(delegate.logins.load(std::sync::atomic::Ordering::SeqCst), 1);
+                assert_eq!(
+                    backend.username(self_rc.borrow().session.as_ref().unwrap()),
+                    "bob"
+                );
+                assert!(self_rc.borrow().pending_setup.is_none());
+
+                // Releasing the stalled first connect afterwards must not
+                // resurrect alice's login -- the setup was aborted, not
+                // merely outraced.
+                let _ = release_first_connect.send(());
+                tokio::task::yield_now().await;
+                tokio::task::yield_now().await;
+
+                assert_eq!(delegate.logins.load(std::sync::atomic::Ordering::SeqCst), 1);
+                assert_eq!(
+                    backend.username(self_rc.borrow().session.as_ref().unwrap()),
+                    "bob"
+                );
+            })
+            .await;
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn password_login_schedules_and_fires_a_proactive_token_refresh() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let delegate = Arc::new(FakeDelegate::new());
+                let player = SpotifyPlayerBuilder::new(FakeBackend::new(), SpotifyPlayerSettings::default())
+                    .delegate(Arc::clone(&delegate) as Arc<dyn SpotifyPlayerDelegate + Send + Sync>)
+                    .build();
+                let self_rc = Rc::new(RefCell::new(player));
+
+                drain_once(
+                    &self_rc,
+                    Command::PasswordLogin {
+                        username: "alice".to_string(),
+                        password: "secret".to_string(),
+                    },
+                )
+                .await;
+
+                assert!(self_rc.borrow().token_refresh_task.is_some());
+
+                // The fake token always expires "now", so the margin makes the
+                // scheduled refresh immediately due; give the timer a moment
+                // of real time to actually fire.
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                tokio::task::yield_now().await;
+
+                assert!(delegate.refreshes.load(std::sync::atomic::Ordering::SeqCst) >= 1);
+                assert!(self_rc.borrow().token_cache.is_some());
+            })
+            .await;
+    }
+}